@@ -62,4 +62,6 @@ pub struct Status {
     pub change_time: DateTime<Utc>,
     #[serde_as(as = "DisplayFromStr")]
     pub commit_hash: Hash,
+    #[serde(default)]
+    pub archived: Option<bool>,
 }
@@ -0,0 +1,154 @@
+use std::env;
+use std::str::FromStr;
+
+/// A locale `Summarise` can render in. Adding a language is a `Catalog`, not
+/// a `stats.rs` change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Fr,
+    De,
+}
+
+impl Locale {
+    /// Detect the locale from `LC_ALL`/`LC_MESSAGES`/`LANG`, defaulting to English.
+    pub fn detect() -> Self {
+        for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            if let Ok(val) = env::var(var) {
+                if let Some(locale) = Self::parse(&val) {
+                    return locale;
+                }
+            }
+        }
+        Self::En
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let lang = s.split(['_', '.', '-']).next()?.to_lowercase();
+        match lang.as_str() {
+            "fr" => Some(Self::Fr),
+            "de" => Some(Self::De),
+            "en" | "" | "c" | "posix" => Some(Self::En),
+            _ => None,
+        }
+    }
+
+    pub fn catalog(self) -> Catalog {
+        match self {
+            Self::En => Catalog {
+                less_than: "< ",
+                and_over: " +",
+                group_sep: ',',
+                units: UnitWords {
+                    minute: ("Minute", "Minutes"),
+                    hour: ("Hour", "Hours"),
+                    day: ("Day", "Days"),
+                    week: ("Week", "Weeks"),
+                    month: ("Month", "Months"),
+                    year: ("Year", "Years"),
+                },
+                captions: Captions {
+                    total: "Total",
+                    skipped: "Skipped",
+                    mean_age: "Mean age",
+                    median_age: "Median age",
+                    p90_age: "P90 age",
+                    p99_age: "P99 age",
+                },
+            },
+            Self::Fr => Catalog {
+                less_than: "< ",
+                and_over: " et plus",
+                group_sep: ' ',
+                units: UnitWords {
+                    minute: ("minute", "minutes"),
+                    hour: ("heure", "heures"),
+                    day: ("jour", "jours"),
+                    week: ("semaine", "semaines"),
+                    month: ("mois", "mois"),
+                    year: ("an", "ans"),
+                },
+                captions: Captions {
+                    total: "Total",
+                    skipped: "Ignorés",
+                    mean_age: "Âge moyen",
+                    median_age: "Âge médian",
+                    p90_age: "Âge P90",
+                    p99_age: "Âge P99",
+                },
+            },
+            Self::De => Catalog {
+                less_than: "< ",
+                and_over: " und mehr",
+                group_sep: '.',
+                units: UnitWords {
+                    minute: ("Minute", "Minuten"),
+                    hour: ("Stunde", "Stunden"),
+                    day: ("Tag", "Tage"),
+                    week: ("Woche", "Wochen"),
+                    month: ("Monat", "Monate"),
+                    year: ("Jahr", "Jahre"),
+                },
+                captions: Captions {
+                    total: "Gesamt",
+                    skipped: "Übersprungen",
+                    mean_age: "Durchschnittsalter",
+                    median_age: "Medianalter",
+                    p90_age: "P90-Alter",
+                    p99_age: "P99-Alter",
+                },
+            },
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).ok_or_else(|| format!("Unsupported locale '{s}'"))
+    }
+}
+
+/// Singular/plural word pairs for each duration unit `stats` bucketises by.
+#[derive(Debug, Clone, Copy)]
+pub struct UnitWords {
+    pub minute: (&'static str, &'static str),
+    pub hour: (&'static str, &'static str),
+    pub day: (&'static str, &'static str),
+    pub week: (&'static str, &'static str),
+    pub month: (&'static str, &'static str),
+    pub year: (&'static str, &'static str),
+}
+
+/// Labels for the fixed (non-bucket) summary lines `Summarise` prints.
+#[derive(Debug, Clone, Copy)]
+pub struct Captions {
+    pub total: &'static str,
+    pub skipped: &'static str,
+    pub mean_age: &'static str,
+    pub median_age: &'static str,
+    pub p90_age: &'static str,
+    pub p99_age: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Catalog {
+    pub less_than: &'static str,
+    pub and_over: &'static str,
+    pub group_sep: char,
+    pub units: UnitWords,
+    pub captions: Captions,
+}
+
+/// Group an integer's digits by thousands using the catalog's separator.
+pub fn group_digits(n: u64, sep: char) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(sep);
+        }
+        out.push(c);
+    }
+    out
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::{Hash, Map, Status};
+
+/// Size in bytes of one index record: a `u64` byte-offset + `u64` length.
+const INDEX_RECORD_LEN: u64 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EventKind {
+    Check,
+    Record,
+    Archive,
+    Unarchive,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Event {
+    pub name: String,
+    pub kind: EventKind,
+    /// Wall-clock moment this event was appended.
+    pub timestamp: DateTime<Utc>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub commit_hash: Option<Hash>,
+    /// For `Record` events, the commit's own timestamp.
+    pub change_time: Option<DateTime<Utc>>,
+}
+
+impl Event {
+    pub fn check(name: String) -> Self {
+        Self {
+            name,
+            kind: EventKind::Check,
+            timestamp: Utc::now(),
+            commit_hash: None,
+            change_time: None,
+        }
+    }
+
+    pub fn record(name: String, commit_hash: Hash, change_time: DateTime<Utc>) -> Self {
+        Self {
+            name,
+            kind: EventKind::Record,
+            timestamp: Utc::now(),
+            commit_hash: Some(commit_hash),
+            change_time: Some(change_time),
+        }
+    }
+
+    pub fn archive(name: String) -> Self {
+        Self {
+            name,
+            kind: EventKind::Archive,
+            timestamp: Utc::now(),
+            commit_hash: None,
+            change_time: None,
+        }
+    }
+
+    pub fn unarchive(name: String) -> Self {
+        Self {
+            name,
+            kind: EventKind::Unarchive,
+            timestamp: Utc::now(),
+            commit_hash: None,
+            change_time: None,
+        }
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.kind, &self.commit_hash, &self.change_time) {
+            (EventKind::Check, _, _) => write!(f, "{} Check", self.timestamp),
+            (EventKind::Record, Some(h), Some(t)) => {
+                write!(f, "{} Record {h} (commit time {t})", self.timestamp)
+            }
+            (EventKind::Record, Some(h), None) => write!(f, "{} Record {h}", self.timestamp),
+            (EventKind::Record, None, _) => write!(f, "{} Record", self.timestamp),
+            (EventKind::Archive, _, _) => write!(f, "{} Archive", self.timestamp),
+            (EventKind::Unarchive, _, _) => write!(f, "{} Unarchive", self.timestamp),
+        }
+    }
+}
+
+/// Append-only event store: events are serialized into `data`, with their
+/// byte-offset/length pushed onto the fixed-width `index` alongside them.
+pub struct Ledger {
+    index: File,
+    data: File,
+}
+
+impl Ledger {
+    pub fn open(dir: &Path) -> std::io::Result<Self> {
+        create_dir_all(dir)?;
+        let index = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(dir.join("index"))?;
+        let data = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(dir.join("data"))?;
+        Ok(Self { index, data })
+    }
+
+    pub fn len(&self) -> std::io::Result<u64> {
+        Ok(self.index.metadata()?.len() / INDEX_RECORD_LEN)
+    }
+
+    pub fn append(&mut self, event: &Event) -> Result<(), Box<dyn Error>> {
+        let bytes = bincode::serialize(event)?;
+        let offset = self.data.seek(SeekFrom::End(0))?;
+        self.data.write_all(&bytes)?;
+        self.index.seek(SeekFrom::End(0))?;
+        self.index.write_all(&offset.to_le_bytes())?;
+        self.index.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        Ok(())
+    }
+
+    pub fn read(&mut self, i: u64) -> Result<Event, Box<dyn Error>> {
+        self.index.seek(SeekFrom::Start(i * INDEX_RECORD_LEN))?;
+        let mut header = [0u8; INDEX_RECORD_LEN as usize];
+        self.index.read_exact(&mut header)?;
+        let offset = u64::from_le_bytes(header[..8].try_into().expect("8 bytes"));
+        let length = u64::from_le_bytes(header[8..].try_into().expect("8 bytes"));
+        self.data.seek(SeekFrom::Start(offset))?;
+        let mut buf = vec![0u8; length as usize];
+        self.data.read_exact(&mut buf)?;
+        Ok(bincode::deserialize(&buf)?)
+    }
+
+    /// Replay every event in append order.
+    pub fn events(&mut self) -> Result<Vec<Event>, Box<dyn Error>> {
+        (0..self.len()?).map(|i| self.read(i)).collect()
+    }
+
+    /// Fold the ledger down to the last-event-per-name view `Summarise`/`Check`
+    /// expect. Archive state is tracked independently of `Record`, so an
+    /// `Archive` that predates the first `Record` for a name isn't lost.
+    pub fn fold_status(&mut self) -> Result<Map, Box<dyn Error>> {
+        let mut map = Map::new();
+        let mut archived: HashMap<String, bool> = HashMap::new();
+        for ev in self.events()? {
+            match ev.kind {
+                EventKind::Record => {
+                    let hash = ev
+                        .commit_hash
+                        .clone()
+                        .ok_or("Record event is missing its commit hash")?;
+                    let change_time = ev
+                        .change_time
+                        .ok_or("Record event is missing its commit's change_time")?;
+                    let seen_archived = archived.get(&ev.name).copied();
+                    map.entry(ev.name)
+                        .and_modify(|s: &mut Status| {
+                            s.commit_hash = hash.clone();
+                            s.change_time = change_time;
+                            s.check_time = ev.timestamp;
+                        })
+                        .or_insert_with(|| Status {
+                            commit_hash: hash,
+                            change_time,
+                            check_time: ev.timestamp,
+                            archived: seen_archived,
+                        });
+                }
+                EventKind::Check => {
+                    if let Some(s) = map.get_mut(&ev.name) {
+                        s.check_time = ev.timestamp;
+                    }
+                }
+                EventKind::Archive => {
+                    archived.insert(ev.name.clone(), true);
+                    if let Some(s) = map.get_mut(&ev.name) {
+                        s.archived = Some(true);
+                    }
+                }
+                EventKind::Unarchive => {
+                    archived.insert(ev.name.clone(), false);
+                    if let Some(s) = map.get_mut(&ev.name) {
+                        s.archived = Some(false);
+                    }
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("prob-check-repo-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn append_and_read_round_trip() {
+        let dir = temp_dir("append-read");
+        let mut ledger = Ledger::open(&dir).expect("open ledger");
+        let ev = Event::check("repo".to_string());
+        ledger.append(&ev).expect("append event");
+        assert_eq!(ledger.read(0).expect("read event"), ev);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fold_status_replays_record_and_check_events() {
+        let dir = temp_dir("fold-status");
+        let mut ledger = Ledger::open(&dir).expect("open ledger");
+        let hash: Hash = "a".repeat(40).parse().expect("valid sha1 hash");
+        let change_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        ledger
+            .append(&Event::record("repo".into(), hash.clone(), change_time))
+            .expect("append record event");
+        ledger
+            .append(&Event::check("repo".into()))
+            .expect("append check event");
+        let map = ledger.fold_status().expect("fold status");
+        let status = map.get("repo").expect("status present");
+        assert_eq!(status.commit_hash, hash);
+        assert_eq!(status.change_time, change_time);
+        assert!(status.check_time >= status.change_time);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fold_status_keeps_archive_that_precedes_first_record() {
+        let dir = temp_dir("archive-before-record");
+        let mut ledger = Ledger::open(&dir).expect("open ledger");
+        ledger
+            .append(&Event::archive("repo".into()))
+            .expect("append archive event");
+        let hash: Hash = "b".repeat(40).parse().expect("valid sha1 hash");
+        ledger
+            .append(&Event::record("repo".into(), hash, Utc::now()))
+            .expect("append record event");
+        let map = ledger.fold_status().expect("fold status");
+        assert_eq!(map.get("repo").expect("status present").archived, Some(true));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
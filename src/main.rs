@@ -1,25 +1,22 @@
 use std::{
-    collections::BTreeMap,
     error::Error,
-    fs::{create_dir_all, read_to_string},
-    io::ErrorKind,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::ExitCode,
+    sync::Mutex,
 };
 
 use chrono::{DateTime, FixedOffset, TimeZone, Utc};
 use clap::{Parser, Subcommand};
 use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
 
-use prob_check_repo::{Hash, Status};
-
-type Map = BTreeMap<String, Status>;
+use prob_check_repo::{Event, Hash, Ledger, Status};
 
 #[derive(Parser, Debug, Clone)]
 #[command(version, about, long_about = None)]
 struct Options {
     #[arg(short, long)]
-    data_file: PathBuf,
+    data_dir: PathBuf,
 
     #[command(subcommand)]
     command: Command,
@@ -36,17 +33,126 @@ enum Command {
     Record {
         #[arg(short = 'n', long = "name")]
         name: String,
-        #[arg(short = 't', long)]
-        commit_time: DateTime<FixedOffset>,
-        #[arg(short = 'c', long)]
-        commit_hash: Hash,
+        #[arg(short = 't', long, required_unless_present = "from_git")]
+        commit_time: Option<DateTime<FixedOffset>>,
+        #[arg(short = 'c', long, required_unless_present = "from_git")]
+        commit_hash: Option<Hash>,
+        /// Resolve the commit hash and time from this git repository's HEAD
+        /// instead of (or as a default for) the manual flags above.
+        #[arg(long)]
+        from_git: Option<PathBuf>,
+    },
+    History {
+        #[arg(short = 'n', long = "name")]
+        name: String,
+    },
+    Archive {
+        #[arg(short = 'n', long = "name")]
+        name: String,
+    },
+    Unarchive {
+        #[arg(short = 'n', long = "name")]
+        name: String,
+    },
+    CheckAll {
+        #[arg(short, long)]
+        manifest: PathBuf,
+        #[arg(short, long)]
+        seed: Option<String>,
+    },
+    RecordAll {
+        #[arg(short, long)]
+        manifest: PathBuf,
     },
     Summarise {
         #[command(subcommand)]
         ty: Summary,
+        /// Override the histogram bucket edges as a comma-separated list of
+        /// human durations, e.g. `1d,3d,1w,3w,3mo,1y`. Takes precedence over
+        /// `buckets` in `--config`.
+        #[arg(long, value_delimiter = ',')]
+        buckets: Option<Vec<String>>,
+        /// TOML file to read a `buckets` override from when `--buckets` isn't
+        /// given on the command line.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Override the detected locale for labels and number formatting.
+        #[arg(long)]
+        locale: Option<prob_check_repo::Locale>,
     },
 }
 
+/// Config file read by `Summarise --config`.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SummariseConfig {
+    buckets: Option<Vec<String>>,
+}
+
+fn read_summarise_config(path: &Path) -> Result<SummariseConfig, Box<dyn Error>> {
+    let s = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&s)?)
+}
+
+/// One entry in a `check-all`/`record-all` manifest: a tracked repo name,
+/// optionally paired with the git checkout to resolve its commit from.
+#[derive(Debug, Clone, Deserialize)]
+struct ManifestEntry {
+    name: String,
+    from_git: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Manifest {
+    #[serde(rename = "repo")]
+    repos: Vec<ManifestEntry>,
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest, Box<dyn Error>> {
+    let s = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&s)?)
+}
+
+/// Run `f` over `items` on a small worker pool, returning results in the
+/// same order as `items` regardless of which worker finishes first.
+fn parallel_map<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let workers = std::thread::available_parallelism()
+        .map(std::num::NonZeroUsize::get)
+        .unwrap_or(4);
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let item = queue.lock().expect("queue lock poisoned").next();
+                let Some((i, item)) = item else { break };
+                let r = f(item);
+                results.lock().expect("results lock poisoned").push((i, r));
+            });
+        }
+    });
+    let mut results = results.into_inner().expect("results lock poisoned");
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Print a SUCCESS/FAILURE tally and report a nonzero exit if anything failed.
+fn summarise_and_exit(results: &[(String, bool)]) -> ExitCode {
+    let total = results.len();
+    let success = results.iter().filter(|(_, ok)| *ok).count();
+    let failure = total - success;
+    println!("{success}/{total} SUCCESS, {failure}/{total} FAILURE");
+    if failure > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
 #[derive(Debug, Clone, Copy, Subcommand)]
 enum Summary {
     RepoAge,
@@ -54,30 +160,29 @@ enum Summary {
 }
 
 impl Options {
-    fn get_config(&self) -> Result<Option<Map>, Box<dyn Error>> {
-        let s = match read_to_string(&self.data_file) {
-            Ok(s) => s,
-            Err(e) => {
-                return if e.kind() == ErrorKind::NotFound {
-                    Ok(None)
-                } else {
-                    Err(e)?
-                };
-            }
-        };
-        Ok(toml::from_str(&s)?)
-    }
-    fn write(&self, config: &Map) -> Result<(), Box<dyn Error>> {
-        let p = self.data_file.canonicalize()?;
-        if let Some(d) = p.parent() {
-            create_dir_all(d)?;
-        }
-        log::debug!("Writing config to {}", p.display());
-        std::fs::write(p, toml::to_string(config)?)?;
-        Ok(())
+    fn open_ledger(&self) -> Result<Ledger, Box<dyn Error>> {
+        Ok(Ledger::open(&self.data_dir)?)
     }
 }
 
+/// Resolve the commit hash and timestamp to record from a git repository's
+/// HEAD commit. The hash is parsed through [`Hash::from_str`] so SHA-1 and
+/// SHA-256 object formats are both handled exactly as they are for the
+/// manual `--commit-hash` flag.
+fn resolve_from_git(path: &Path) -> Result<(Hash, DateTime<FixedOffset>), Box<dyn Error>> {
+    let repo = git2::Repository::open(path)?;
+    let commit = repo.head()?.peel_to_commit()?;
+    let hash: Hash = commit.id().to_string().parse()?;
+    let time = commit.time();
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)
+        .ok_or("Invalid commit timezone offset")?;
+    let commit_time = offset
+        .timestamp_opt(time.seconds(), 0)
+        .single()
+        .ok_or("Invalid commit timestamp")?;
+    Ok((hash, commit_time))
+}
+
 #[allow(clippy::cast_precision_loss)]
 fn calculate_probability<Tz: TimeZone>(
     last_change: DateTime<Tz>,
@@ -116,19 +221,15 @@ fn get_rng<T: AsRef<[u8]>>(seed: Option<T>) -> StdRng {
     StdRng::from_rng(rand::thread_rng()).expect("Should create StdRng")
 }
 
-fn do_check<T: AsRef<[u8]>>(seed: Option<T>, status: Option<Status>) -> ExitCode {
+fn do_check<T: AsRef<[u8]>>(seed: Option<T>, status: Option<Status>) -> bool {
     if let Some(st) = status {
         if st.archived.unwrap_or(false) {
-            return ExitCode::FAILURE;
+            return false;
         }
         let mut rng = get_rng(seed);
-        if should_run_now(&mut rng, st.change_time, st.check_time) {
-            ExitCode::SUCCESS
-        } else {
-            ExitCode::FAILURE
-        }
+        should_run_now(&mut rng, st.change_time, st.check_time)
     } else {
-        ExitCode::SUCCESS
+        true
     }
 }
 
@@ -137,45 +238,177 @@ fn main() -> ExitCode {
     let args = Options::parse();
     match args.command {
         Command::Check { ref seed, ref name } => {
-            return do_check(
-                seed.as_ref(),
-                args.get_config()
-                    .expect("Should config")
-                    .and_then(|mut m| m.remove(name)),
-            );
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            let status = ledger
+                .fold_status()
+                .expect("Should replay ledger")
+                .remove(name);
+            let ok = do_check(seed.as_ref(), status);
+            ledger
+                .append(&Event::check(name.to_owned()))
+                .expect("Should append check event");
+            if ok {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
         }
         Command::Record {
             ref name,
             ref commit_hash,
             ref commit_time,
+            ref from_git,
         } => {
-            let mut conf = args
-                .get_config()
-                .expect("Should read config")
-                .unwrap_or_else(Map::default);
-            log::debug!("Updating status for {name}");
-            conf.entry(name.to_owned())
-                .and_modify(|s| {
-                    s.commit_hash = commit_hash.clone();
-                    s.change_time = commit_time.to_utc();
-                    s.check_time = Utc::now();
-                })
-                .or_insert_with(|| Status {
-                    commit_hash: commit_hash.to_owned(),
-                    change_time: commit_time.to_utc(),
-                    check_time: Utc::now(),
-                    archived: None,
-                });
-            args.write(&conf).expect("Should write config to file");
+            let (commit_hash, commit_time) = match from_git {
+                Some(path) => {
+                    let (git_hash, git_time) =
+                        resolve_from_git(path).expect("Should read git repository");
+                    (
+                        commit_hash.clone().unwrap_or(git_hash),
+                        commit_time.unwrap_or(git_time),
+                    )
+                }
+                None => (
+                    commit_hash
+                        .clone()
+                        .expect("commit-hash is required without --from-git"),
+                    commit_time.expect("commit-time is required without --from-git"),
+                ),
+            };
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            log::debug!("Recording status for {name}");
+            ledger
+                .append(&Event::record(
+                    name.to_owned(),
+                    commit_hash,
+                    commit_time.to_utc(),
+                ))
+                .expect("Should append record event");
+            ExitCode::SUCCESS
+        }
+        Command::History { ref name } => {
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            for ev in ledger.events().expect("Should replay ledger") {
+                if &ev.name == name {
+                    println!("{ev}");
+                }
+            }
             ExitCode::SUCCESS
         }
-        Command::Summarise { ty } => {
-            let v = args.get_config().unwrap().expect("Data file not found");
+        Command::Archive { ref name } => {
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            ledger
+                .append(&Event::archive(name.to_owned()))
+                .expect("Should append archive event");
+            ExitCode::SUCCESS
+        }
+        Command::Unarchive { ref name } => {
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            ledger
+                .append(&Event::unarchive(name.to_owned()))
+                .expect("Should append unarchive event");
+            ExitCode::SUCCESS
+        }
+        Command::CheckAll {
+            ref manifest,
+            ref seed,
+        } => {
+            let manifest = read_manifest(manifest).expect("Should read manifest");
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            let map = ledger.fold_status().expect("Should replay ledger");
+            let results = parallel_map(manifest.repos, |entry| {
+                let entry_seed = seed.as_ref().map(|s| format!("{s}:{}", entry.name));
+                let ok = do_check(entry_seed.as_ref(), map.get(&entry.name).cloned());
+                (entry.name, ok)
+            });
+            for (name, _) in &results {
+                ledger
+                    .append(&Event::check(name.clone()))
+                    .expect("Should append check event");
+            }
+            summarise_and_exit(&results)
+        }
+        Command::RecordAll { ref manifest } => {
+            let manifest = read_manifest(manifest).expect("Should read manifest");
+            let resolved = parallel_map(manifest.repos, |entry| {
+                let update = match entry.from_git.as_deref() {
+                    Some(path) => match resolve_from_git(path) {
+                        Ok(v) => Some(v),
+                        Err(e) => {
+                            log::error!("Failed to resolve {} from git: {e}", entry.name);
+                            None
+                        }
+                    },
+                    None => {
+                        log::error!("{} has no from_git path in the manifest", entry.name);
+                        None
+                    }
+                };
+                (entry.name, update)
+            });
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            let results = resolved
+                .into_iter()
+                .map(|(name, update)| match update {
+                    Some((hash, time)) => {
+                        ledger
+                            .append(&Event::record(name.clone(), hash, time.to_utc()))
+                            .expect("Should append record event");
+                        (name, true)
+                    }
+                    None => (name, false),
+                })
+                .collect::<Vec<_>>();
+            summarise_and_exit(&results)
+        }
+        Command::Summarise {
+            ty,
+            ref buckets,
+            ref config,
+            locale,
+        } => {
+            let config_buckets = config
+                .as_deref()
+                .map(|path| read_summarise_config(path).expect("Should read --config"))
+                .and_then(|c| c.buckets);
+            let raw_buckets = buckets.clone().or(config_buckets);
+            let edges = match raw_buckets {
+                Some(raw) => {
+                    let mut edges = raw
+                        .iter()
+                        .map(|s| prob_check_repo::parse_duration(s))
+                        .collect::<Result<Vec<_>, _>>()
+                        .expect("Should parse bucket durations");
+                    edges.sort_unstable();
+                    edges
+                }
+                None => prob_check_repo::default_buckets(),
+            };
+            let catalog = locale.unwrap_or_else(prob_check_repo::Locale::detect).catalog();
+            let mut ledger = args.open_ledger().expect("Should open ledger");
+            let v = ledger.fold_status().expect("Should replay ledger");
             match ty {
-                Summary::RepoAge => prob_check_repo::summary_repo_age(v.values(), true),
-                Summary::CheckTime => prob_check_repo::summary_check_age(v.values()),
+                Summary::RepoAge => {
+                    prob_check_repo::summary_repo_age(v.values(), true, &edges, &catalog);
+                }
+                Summary::CheckTime => {
+                    prob_check_repo::summary_check_age(v.values(), &edges, &catalog);
+                }
             }
             ExitCode::SUCCESS
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_map_preserves_input_order() {
+        let items: Vec<u32> = (0..50).collect();
+        let results = parallel_map(items.clone(), |i| i * 2);
+        let expected: Vec<u32> = items.iter().map(|i| i * 2).collect();
+        assert_eq!(results, expected);
+    }
+}
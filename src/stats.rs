@@ -0,0 +1,238 @@
+use chrono::{DateTime, Utc};
+
+use crate::i18n::{group_digits, Catalog};
+use crate::Status;
+
+// num of minutes in each, roughly...
+const HOURS: u64 = 60;
+const DAYS: u64 = HOURS * 24;
+const WEEKS: u64 = DAYS * 7;
+const MONTHS: u64 = DAYS * 30;
+const YEARS: u64 = DAYS * 365;
+
+/// The bucket edges `Summarise` uses when neither `--buckets` nor `--config`
+/// supplies any.
+pub fn default_buckets() -> Vec<u64> {
+    vec![
+        (24 * HOURS),
+        (3 * DAYS),
+        WEEKS,
+        (3 * WEEKS),
+        (3 * MONTHS),
+        YEARS,
+        (3 * YEARS),
+        (10 * YEARS),
+    ]
+}
+
+/// Parse a human duration like `3d`, `2w`, or `6mo` into a number of minutes.
+pub fn parse_duration(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split_at = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("Duration '{s}' is missing a unit suffix"))?;
+    let (num, unit) = s.split_at(split_at);
+    let n: u64 = num
+        .parse()
+        .map_err(|e| format!("Could not parse duration '{s}': {e}"))?;
+    let minutes_per_unit = match unit {
+        "m" | "min" | "mins" => 1,
+        "h" | "hr" | "hrs" => HOURS,
+        "d" | "day" | "days" => DAYS,
+        "w" | "wk" | "wks" => WEEKS,
+        "mo" | "mon" | "month" | "months" => MONTHS,
+        "y" | "yr" | "yrs" | "year" | "years" => YEARS,
+        other => return Err(format!("Unknown duration unit '{other}' in '{s}'")),
+    };
+    Ok(n * minutes_per_unit)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Unit {
+    Year,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+}
+
+/// Split minutes into the largest duration unit that divides it evenly.
+fn duration_parts(minutes: u64) -> (u64, Unit) {
+    const UNITS: [(u64, Unit); 4] = [
+        (YEARS, Unit::Year),
+        (MONTHS, Unit::Month),
+        (WEEKS, Unit::Week),
+        (DAYS, Unit::Day),
+    ];
+    for (unit_minutes, unit) in UNITS {
+        if minutes >= unit_minutes && minutes % unit_minutes == 0 {
+            return (minutes / unit_minutes, unit);
+        }
+    }
+    if minutes >= HOURS && minutes % HOURS == 0 {
+        (minutes / HOURS, Unit::Hour)
+    } else {
+        (minutes, Unit::Minute)
+    }
+}
+
+/// Render a duration through the catalog, picking the plural form by count.
+fn format_duration(minutes: u64, catalog: &Catalog) -> String {
+    let (count, unit) = duration_parts(minutes);
+    let (singular, plural) = match unit {
+        Unit::Year => catalog.units.year,
+        Unit::Month => catalog.units.month,
+        Unit::Week => catalog.units.week,
+        Unit::Day => catalog.units.day,
+        Unit::Hour => catalog.units.hour,
+        Unit::Minute => catalog.units.minute,
+    };
+    let word = if count == 1 { singular } else { plural };
+    format!("{} {word}", group_digits(count, catalog.group_sep))
+}
+
+fn bucket_labels(edges: &[u64], catalog: &Catalog) -> Vec<String> {
+    let mut labels: Vec<String> = edges
+        .iter()
+        .map(|e| format!("{}{}", catalog.less_than, format_duration(*e, catalog)))
+        .collect();
+    labels.push(format!(
+        "{}{}",
+        format_duration(*edges.last().expect("edges is non-empty"), catalog),
+        catalog.and_over
+    ));
+    labels
+}
+
+fn mean_minutes(sorted: &[u64]) -> Option<u64> {
+    if sorted.is_empty() {
+        None
+    } else {
+        Some(sorted.iter().sum::<u64>() / sorted.len() as u64)
+    }
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn percentile_minutes(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted.get(rank).copied()
+}
+
+pub fn summary_repo_age<'a, I>(it: I, ignore_archived: bool, edges: &[u64], catalog: &Catalog)
+where
+    I: IntoIterator<Item = &'a Status>,
+{
+    summarise_age_by(
+        it,
+        |st| {
+            if ignore_archived && st.archived.unwrap_or(false) {
+                None
+            } else {
+                Some(st.change_time)
+            }
+        },
+        edges,
+        catalog,
+    );
+}
+
+pub fn summary_check_age<'a, I>(it: I, edges: &[u64], catalog: &Catalog)
+where
+    I: IntoIterator<Item = &'a Status>,
+{
+    summarise_age_by(it, |st| Some(st.check_time), edges, catalog);
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn summarise_age_by<'a, I, F>(it: I, by: F, edges: &[u64], catalog: &Catalog)
+where
+    I: IntoIterator<Item = &'a Status>,
+    F: Fn(&'a Status) -> Option<DateTime<Utc>>,
+{
+    let now = Utc::now();
+    let mut counters = vec![0u64; edges.len() + 1];
+    let mut ages: Vec<u64> = Vec::new();
+    let mut total = 0u64;
+    let mut skipped = 0u64;
+    for st in it {
+        total += 1;
+        match by(st) {
+            Some(t) => {
+                let ch = (now - t).num_minutes();
+                assert!(ch >= 0, "Time in future: {:?}!", t);
+                let ch = ch as u64;
+                let ix = bisection::bisect_left(edges, &ch);
+                counters[ix] += 1;
+                ages.push(ch);
+            }
+            None => skipped += 1,
+        }
+    }
+    ages.sort_unstable();
+    for (label, count) in bucket_labels(edges, catalog).iter().zip(counters.iter()) {
+        println!("{label}: {}", group_digits(*count, catalog.group_sep));
+    }
+    let captions = &catalog.captions;
+    println!("{}: {}", captions.total, group_digits(total, catalog.group_sep));
+    println!("{}: {}", captions.skipped, group_digits(skipped, catalog.group_sep));
+    if let Some(mean) = mean_minutes(&ages) {
+        println!("{}: {}", captions.mean_age, format_duration(mean, catalog));
+    }
+    if let Some(median) = percentile_minutes(&ages, 50.0) {
+        println!("{}: {}", captions.median_age, format_duration(median, catalog));
+    }
+    if let Some(p90) = percentile_minutes(&ages, 90.0) {
+        println!("{}: {}", captions.p90_age, format_duration(p90, catalog));
+    }
+    if let Some(p99) = percentile_minutes(&ages, 99.0) {
+        println!("{}: {}", captions.p99_age, format_duration(p99, catalog));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_supports_each_unit_suffix() {
+        assert_eq!(parse_duration("30m").unwrap(), 30);
+        assert_eq!(parse_duration("2h").unwrap(), 2 * HOURS);
+        assert_eq!(parse_duration("3d").unwrap(), 3 * DAYS);
+        assert_eq!(parse_duration("2w").unwrap(), 2 * WEEKS);
+        assert_eq!(parse_duration("6mo").unwrap(), 6 * MONTHS);
+        assert_eq!(parse_duration("1y").unwrap(), YEARS);
+    }
+
+    #[test]
+    fn parse_duration_rejects_missing_or_unknown_unit() {
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("10x").is_err());
+    }
+
+    #[test]
+    fn mean_minutes_is_none_for_empty_and_averages_otherwise() {
+        assert_eq!(mean_minutes(&[]), None);
+        assert_eq!(mean_minutes(&[10, 20, 30]), Some(20));
+    }
+
+    #[test]
+    fn percentile_minutes_is_none_for_empty_slice() {
+        assert_eq!(percentile_minutes(&[], 50.0), None);
+    }
+
+    #[test]
+    fn percentile_minutes_picks_nearest_rank() {
+        let sorted = [10, 20, 30, 40, 50];
+        assert_eq!(percentile_minutes(&sorted, 0.0), Some(10));
+        assert_eq!(percentile_minutes(&sorted, 100.0), Some(50));
+    }
+
+    #[test]
+    fn percentile_minutes_handles_single_element() {
+        assert_eq!(percentile_minutes(&[42], 90.0), Some(42));
+    }
+}